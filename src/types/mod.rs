@@ -1,6 +1,9 @@
 //! Types module.
 
+pub mod mutf8;
 pub mod read;
+pub mod write;
+use self::mutf8::{DecodeError, StringPool};
 use self::read::ClassData;
 use crate::error;
 use bitflags::bitflags;
@@ -80,6 +83,36 @@ impl FromStr for Type {
     }
 }
 
+impl Type {
+    /// Encodes this type back into its canonical DEX type descriptor.
+    ///
+    /// This is the inverse of [`FromStr`]: it produces the exact descriptor bytes DEX stores
+    /// (`I`, `Z`, `Ljava/lang/String;`, `[[I`, ...), unlike `Display`, which renders the
+    /// human-readable Java form.
+    pub fn to_descriptor(&self) -> String {
+        match self {
+            Self::Void => "V".to_owned(),
+            Self::Boolean => "Z".to_owned(),
+            Self::Byte => "B".to_owned(),
+            Self::Short => "S".to_owned(),
+            Self::Char => "C".to_owned(),
+            Self::Int => "I".to_owned(),
+            Self::Long => "J".to_owned(),
+            Self::Float => "F".to_owned(),
+            Self::Double => "D".to_owned(),
+            Self::FullyQualifiedName(name) => format!("L{}", name),
+            Self::Array {
+                dimensions,
+                array_type,
+            } => {
+                let mut descriptor = "[".repeat(*dimensions as usize);
+                descriptor.push_str(&array_type.to_descriptor());
+                descriptor
+            }
+        }
+    }
+}
+
 impl fmt::Display for Type {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -92,11 +125,20 @@ impl fmt::Display for Type {
             Self::Long => write!(f, "long"),
             Self::Float => write!(f, "float"),
             Self::Double => write!(f, "double"),
-            Self::FullyQualifiedName(name) => write!(f, "{}", name),
+            Self::FullyQualifiedName(name) => {
+                let name = name.strip_suffix(';').unwrap_or(name);
+                write!(f, "{}", name.replace('/', "."))
+            }
             Self::Array {
                 dimensions,
                 array_type,
-            } => write!(f, "{}[{}]", array_type, dimensions),
+            } => {
+                write!(f, "{}", array_type)?;
+                for _ in 0..*dimensions {
+                    write!(f, "[]")?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -131,6 +173,21 @@ impl ShortyReturnType {
             _ => Err(error::Parse::InvalidShortyType(c)),
         }
     }
+
+    fn to_char(self) -> char {
+        match self {
+            Self::Void => 'V',
+            Self::Boolean => 'Z',
+            Self::Byte => 'B',
+            Self::Short => 'S',
+            Self::Char => 'C',
+            Self::Int => 'I',
+            Self::Long => 'J',
+            Self::Float => 'F',
+            Self::Double => 'D',
+            Self::Reference => 'L',
+        }
+    }
 }
 
 impl From<Type> for ShortyReturnType {
@@ -194,6 +251,20 @@ impl ShortyFieldType {
             _ => Err(error::Parse::InvalidShortyType(c)),
         }
     }
+
+    fn to_char(self) -> char {
+        match self {
+            Self::Boolean => 'Z',
+            Self::Byte => 'B',
+            Self::Short => 'S',
+            Self::Char => 'C',
+            Self::Int => 'I',
+            Self::Long => 'J',
+            Self::Float => 'F',
+            Self::Double => 'D',
+            Self::Reference => 'L',
+        }
+    }
 }
 
 /// Short form of type descriptor.
@@ -223,6 +294,20 @@ impl FromStr for ShortyDescriptor {
     }
 }
 
+impl ShortyDescriptor {
+    /// Encodes this shorty descriptor back into its DEX string form (e.g. `ILI`).
+    ///
+    /// This is the inverse of [`FromStr`].
+    pub fn to_descriptor(&self) -> String {
+        let mut descriptor = String::with_capacity(1 + self.field_types.len());
+        descriptor.push(self.return_type.to_char());
+        for field_type in self.field_types.iter() {
+            descriptor.push(field_type.to_char());
+        }
+        descriptor
+    }
+}
+
 /// Prototype implementation.
 #[derive(Debug)]
 pub struct Prototype {
@@ -244,6 +329,26 @@ impl Prototype {
             parameters: parameters.into(),
         }
     }
+
+    /// Encodes this prototype back into its shorty descriptor and its full method descriptor.
+    ///
+    /// The shorty descriptor is the condensed form (e.g. `ILI`), while the full descriptor
+    /// spells out every parameter and the return type (e.g. `(Ljava/lang/String;I)I`). Together
+    /// these are the inverse of parsing a prototype from its DEX-encoded parts.
+    pub fn to_descriptor(&self) -> (String, String) {
+        let shorty = self.descriptor.to_descriptor();
+
+        let mut full = String::from("(");
+        if let Some(parameters) = &self.parameters {
+            for parameter in parameters.iter() {
+                full.push_str(&parameter.to_descriptor());
+            }
+        }
+        full.push(')');
+        full.push_str(&self.return_type.to_descriptor());
+
+        (shorty, full)
+    }
 }
 
 /// Annotation visibility.
@@ -294,12 +399,38 @@ pub enum Value {
     Boolean(bool),
 }
 
+impl Value {
+    /// Resolves this value's string contents through `pool`, if it is a [`Value::String`].
+    ///
+    /// Returns `None` for every other variant.
+    pub fn resolve_string(&self, pool: &StringPool<'_>) -> Option<Result<String, DecodeError>> {
+        match self {
+            Self::String(index) => Some(pool.get(*index)),
+            _ => None,
+        }
+    }
+}
+
 /// Array.
 #[derive(Debug, Clone)]
 pub struct Array {
     inner: Box<[Value]>,
 }
 
+impl Array {
+    /// Creates a new array of values.
+    pub fn new<T: Into<Box<[Value]>>>(inner: T) -> Self {
+        Self {
+            inner: inner.into(),
+        }
+    }
+
+    /// Gets the values in the array.
+    pub fn values(&self) -> &[Value] {
+        &self.inner
+    }
+}
+
 /// Annotation element.
 #[derive(Debug, Clone)]
 pub struct AnnotationElement {
@@ -308,10 +439,20 @@ pub struct AnnotationElement {
 }
 
 impl AnnotationElement {
+    /// Creates a new annotation element.
+    pub fn new(name: u32, value: Value) -> Self {
+        Self { name, value }
+    }
+
     /// Gets the index of the name string.
     pub fn name_index(&self) -> u32 {
         self.name
     }
+
+    /// Resolves the element's name through `pool`.
+    pub fn resolve_name(&self, pool: &StringPool<'_>) -> Result<String, DecodeError> {
+        pool.get(self.name)
+    }
 }
 
 impl Deref for AnnotationElement {
@@ -330,6 +471,11 @@ pub struct EncodedAnnotation {
 }
 
 impl EncodedAnnotation {
+    /// Creates a new encoded annotation.
+    pub fn new(type_id: u32, elements: Box<[AnnotationElement]>) -> Self {
+        Self { type_id, elements }
+    }
+
     /// Gets the index of the type of the annotation.
     pub fn type_index(&self) -> u32 {
         self.type_id
@@ -497,8 +643,38 @@ impl ParameterAnnotations {
 }
 
 bitflags! {
-    /// Access flags.
-    pub struct AccessFlags: u32 {
+    /// Access flags that are valid on a class (or `class_def_item`/inner-class) item.
+    ///
+    /// The DEX format reuses bit positions across item kinds (e.g. `0x40` means
+    /// `ACC_VOLATILE` on a field but `ACC_BRIDGE` on a method), so each item kind gets its
+    /// own newtype rather than sharing one flat `AccessFlags` set.
+    pub struct ClassAccessFlags: u32 {
+        /// Public access.
+        const ACC_PUBLIC = 0x1;
+        /// Private access.
+        const ACC_PRIVATE = 0x2;
+        /// Protected access.
+        const ACC_PROTECTED = 0x4;
+        /// Static access.
+        const ACC_STATIC = 0x8;
+        /// Final element (non modifiable).
+        const ACC_FINAL = 0x10;
+        /// Interface.
+        const ACC_INTERFACE = 0x200;
+        /// Abstract element.
+        const ACC_ABSTRACT = 0x400;
+        /// Synthetic.
+        const ACC_SYNTHETIC = 0x1000;
+        /// Annotation.
+        const ACC_ANNOTATION = 0x2000;
+        /// Enum.
+        const ACC_ENUM = 0x4000;
+    }
+}
+
+bitflags! {
+    /// Access flags that are valid on a field (`encoded_field`) item.
+    pub struct FieldAccessFlags: u32 {
         /// Public access.
         const ACC_PUBLIC = 0x1;
         /// Private access.
@@ -509,30 +685,44 @@ bitflags! {
         const ACC_STATIC = 0x8;
         /// Final element (non modifiable).
         const ACC_FINAL = 0x10;
-        /// Thread - synchronized element.
-        const ACC_SYNCHRONIZED = 0x20;
         /// Volatile element.
         const ACC_VOLATILE = 0x40;
-        /// Bridge.
-        const ACC_BRIDGE = 0x40;
         /// Transient.
         const ACC_TRANSIENT = 0x80;
+        /// Synthetic.
+        const ACC_SYNTHETIC = 0x1000;
+        /// Enum.
+        const ACC_ENUM = 0x4000;
+    }
+}
+
+bitflags! {
+    /// Access flags that are valid on a method (`encoded_method`) item.
+    pub struct MethodAccessFlags: u32 {
+        /// Public access.
+        const ACC_PUBLIC = 0x1;
+        /// Private access.
+        const ACC_PRIVATE = 0x2;
+        /// Protected access.
+        const ACC_PROTECTED = 0x4;
+        /// Static access.
+        const ACC_STATIC = 0x8;
+        /// Final element (non modifiable).
+        const ACC_FINAL = 0x10;
+        /// Thread - synchronized element.
+        const ACC_SYNCHRONIZED = 0x20;
+        /// Bridge.
+        const ACC_BRIDGE = 0x40;
         /// Varargs.
         const ACC_VARARGS = 0x80;
         /// Native element.
         const ACC_NATIVE = 0x100;
-        /// Interface.
-        const ACC_INTERFACE = 0x200;
         /// Abstract element.
         const ACC_ABSTRACT = 0x400;
         /// Strict.
         const ACC_STRICT = 0x800;
         /// Synthetic.
         const ACC_SYNTHETIC = 0x1000;
-        /// Annotation.
-        const ACC_ANNOTATION = 0x2000;
-        /// Enum.
-        const ACC_ENUM = 0x4000;
         /// Constructor.
         const ACC_CONSTRUCTOR = 0x10000;
         /// Declared as synchronized element.
@@ -540,7 +730,7 @@ bitflags! {
     }
 }
 
-impl fmt::Display for AccessFlags {
+impl fmt::Display for ClassAccessFlags {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut out = String::new();
 
@@ -564,22 +754,106 @@ impl fmt::Display for AccessFlags {
             out.push_str("final ");
         }
 
-        if self.contains(Self::ACC_SYNCHRONIZED) {
-            out.push_str("synchronized ");
+        if self.contains(Self::ACC_ABSTRACT) {
+            out.push_str("abstract ");
         }
 
-        if self.contains(Self::ACC_VOLATILE) {
-            out.push_str("volatile ");
+        if self.contains(Self::ACC_INTERFACE) {
+            out.push_str("interface ");
         }
 
-        if self.contains(Self::ACC_BRIDGE) {
-            out.push_str("bridge ");
+        if self.contains(Self::ACC_SYNTHETIC) {
+            out.push_str("synthetic ");
+        }
+
+        // if self.contains(Self::ACC_ANNOTATION) {
+        //     out.push_str("annotation ");
+        // }
+
+        if self.contains(Self::ACC_ENUM) {
+            out.push_str("enum ");
+        }
+
+        write!(f, "{}", out.trim())
+    }
+}
+
+impl fmt::Display for FieldAccessFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+
+        if self.contains(Self::ACC_PUBLIC) {
+            out.push_str("public ");
+        }
+
+        if self.contains(Self::ACC_PRIVATE) {
+            out.push_str("private ");
+        }
+
+        if self.contains(Self::ACC_PROTECTED) {
+            out.push_str("protected ");
+        }
+
+        if self.contains(Self::ACC_STATIC) {
+            out.push_str("static ");
+        }
+
+        if self.contains(Self::ACC_FINAL) {
+            out.push_str("final ");
+        }
+
+        if self.contains(Self::ACC_VOLATILE) {
+            out.push_str("volatile ");
         }
 
         if self.contains(Self::ACC_TRANSIENT) {
             out.push_str("transient ");
         }
 
+        if self.contains(Self::ACC_SYNTHETIC) {
+            out.push_str("synthetic ");
+        }
+
+        if self.contains(Self::ACC_ENUM) {
+            out.push_str("enum ");
+        }
+
+        write!(f, "{}", out.trim())
+    }
+}
+
+impl fmt::Display for MethodAccessFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+
+        if self.contains(Self::ACC_PUBLIC) {
+            out.push_str("public ");
+        }
+
+        if self.contains(Self::ACC_PRIVATE) {
+            out.push_str("private ");
+        }
+
+        if self.contains(Self::ACC_PROTECTED) {
+            out.push_str("protected ");
+        }
+
+        if self.contains(Self::ACC_STATIC) {
+            out.push_str("static ");
+        }
+
+        if self.contains(Self::ACC_FINAL) {
+            out.push_str("final ");
+        }
+
+        if self.contains(Self::ACC_SYNCHRONIZED) {
+            out.push_str("synchronized ");
+        }
+
+        if self.contains(Self::ACC_BRIDGE) {
+            out.push_str("bridge ");
+        }
+
         if self.contains(Self::ACC_VARARGS) {
             out.push_str("varargs ");
         }
@@ -592,10 +866,6 @@ impl fmt::Display for AccessFlags {
             out.push_str("abstract ");
         }
 
-        if self.contains(Self::ACC_INTERFACE) {
-            out.push_str("interface ");
-        }
-
         if self.contains(Self::ACC_STRICT) {
             out.push_str("strict ");
         }
@@ -604,14 +874,6 @@ impl fmt::Display for AccessFlags {
             out.push_str("synthetic ");
         }
 
-        // if self.contains(Self::ACC_ANNOTATION) {
-        //     out.push_str("annotation ");
-        // }
-
-        if self.contains(Self::ACC_ENUM) {
-            out.push_str("enum ");
-        }
-
         if self.contains(Self::ACC_CONSTRUCTOR) {
             out.push_str("constructor ");
         }
@@ -628,7 +890,7 @@ impl fmt::Display for AccessFlags {
 #[derive(Debug)]
 pub struct Class {
     class_index: u32,
-    access_flags: AccessFlags,
+    access_flags: ClassAccessFlags,
     superclass_index: Option<u32>,
     interfaces: Box<[Type]>,
     source_file_index: Option<u32>,
@@ -642,7 +904,7 @@ impl Class {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         class_index: u32,
-        access_flags: AccessFlags,
+        access_flags: ClassAccessFlags,
         superclass_index: Option<u32>,
         interfaces: Box<[Type]>,
         source_file_index: Option<u32>,
@@ -668,7 +930,7 @@ impl Class {
     }
 
     /// Gets the access flags of the class.
-    pub fn access_flags(&self) -> AccessFlags {
+    pub fn access_flags(&self) -> ClassAccessFlags {
         self.access_flags
     }
 
@@ -687,6 +949,14 @@ impl Class {
         self.source_file_index
     }
 
+    /// Resolves the name of the source file through `pool`, if it is known.
+    pub fn resolve_source_file(
+        &self,
+        pool: &StringPool<'_>,
+    ) -> Option<Result<String, DecodeError>> {
+        self.source_file_index.map(|index| pool.get(index))
+    }
+
     /// Gets the annotations for the class, if there are any.
     pub fn annotations(&self) -> Option<&AnnotationsDirectory> {
         self.annotations.as_ref()
@@ -710,11 +980,49 @@ impl Class {
 
 #[cfg(test)]
 mod test {
-    use super::AccessFlags;
+    use super::{ClassAccessFlags, FieldAccessFlags, MethodAccessFlags, Type};
+
+    #[test]
+    fn it_can_round_trip_a_simple_type_descriptor() {
+        let descriptor = "I";
+        let parsed: Type = descriptor.parse().unwrap();
+
+        assert_eq!(descriptor, parsed.to_descriptor());
+    }
+
+    #[test]
+    fn it_can_round_trip_a_fully_qualified_name_descriptor() {
+        let descriptor = "Ljava/lang/String;";
+        let parsed: Type = descriptor.parse().unwrap();
+
+        assert_eq!(descriptor, parsed.to_descriptor());
+    }
+
+    #[test]
+    fn it_can_round_trip_a_multidimensional_array_descriptor() {
+        let descriptor = "[[I";
+        let parsed: Type = descriptor.parse().unwrap();
+
+        assert_eq!(descriptor, parsed.to_descriptor());
+    }
+
+    #[test]
+    fn it_displays_a_fully_qualified_name_as_a_dotted_source_name() {
+        let parsed: Type = "Ljava/lang/String;".parse().unwrap();
+
+        assert_eq!("java.lang.String", format!("{}", parsed));
+    }
+
+    #[test]
+    fn it_displays_a_multidimensional_array_with_one_bracket_pair_per_dimension() {
+        let parsed: Type = "[[I".parse().unwrap();
+
+        assert_eq!("int[][]", format!("{}", parsed));
+    }
 
     #[test]
     fn it_can_display_access() {
-        let access = AccessFlags::ACC_PUBLIC;
+        let access = MethodAccessFlags::ACC_PUBLIC;
 
         let display = format!("{}", access);
 
@@ -723,7 +1031,7 @@ mod test {
 
     #[test]
     fn it_can_display_mixed_access_bitflags() {
-        let access = AccessFlags::ACC_PUBLIC | AccessFlags::ACC_DECLARED_SYNCHRONIZED;
+        let access = MethodAccessFlags::ACC_PUBLIC | MethodAccessFlags::ACC_DECLARED_SYNCHRONIZED;
 
         let display = format!("{}", access);
 
@@ -732,8 +1040,9 @@ mod test {
 
     #[test]
     fn it_can_display_mixed_access_bitflags_protected_static_abstract() {
-        let access =
-            AccessFlags::ACC_PROTECTED | AccessFlags::ACC_ABSTRACT | AccessFlags::ACC_STATIC;
+        let access = MethodAccessFlags::ACC_PROTECTED
+            | MethodAccessFlags::ACC_ABSTRACT
+            | MethodAccessFlags::ACC_STATIC;
 
         let display = format!("{}", access);
 
@@ -742,13 +1051,31 @@ mod test {
 
     #[test]
     fn it_can_display_mixed_access_bitflags_public_interface_abstract_annotation() {
-        let access = AccessFlags::ACC_PUBLIC
-            | AccessFlags::ACC_INTERFACE
-            | AccessFlags::ACC_ABSTRACT
-            | AccessFlags::ACC_ANNOTATION;
+        let access = ClassAccessFlags::ACC_PUBLIC
+            | ClassAccessFlags::ACC_INTERFACE
+            | ClassAccessFlags::ACC_ABSTRACT
+            | ClassAccessFlags::ACC_ANNOTATION;
 
         let display = format!("{}", access);
 
         assert_eq!("public abstract interface", display);
     }
+
+    #[test]
+    fn it_disambiguates_bit_0x40_between_fields_and_methods() {
+        let field_access = FieldAccessFlags::ACC_PUBLIC | FieldAccessFlags::ACC_VOLATILE;
+        let method_access = MethodAccessFlags::ACC_PUBLIC | MethodAccessFlags::ACC_BRIDGE;
+
+        assert_eq!("public volatile", format!("{}", field_access));
+        assert_eq!("public bridge", format!("{}", method_access));
+    }
+
+    #[test]
+    fn it_disambiguates_bit_0x80_between_fields_and_methods() {
+        let field_access = FieldAccessFlags::ACC_PUBLIC | FieldAccessFlags::ACC_TRANSIENT;
+        let method_access = MethodAccessFlags::ACC_PUBLIC | MethodAccessFlags::ACC_VARARGS;
+
+        assert_eq!("public transient", format!("{}", field_access));
+        assert_eq!("public varargs", format!("{}", method_access));
+    }
 }
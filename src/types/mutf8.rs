@@ -0,0 +1,212 @@
+//! Modified UTF-8 (MUTF-8) decoding.
+//!
+//! DEX stores string data in a variant of CESU-8, not plain UTF-8: the NUL character is
+//! encoded as the two-byte sequence `0xC0 0x80` (so a decoded string can still be
+//! NUL-terminated), and supplementary (astral) code points are encoded as a surrogate pair
+//! where each surrogate is written as its own three-byte sequence, rather than being joined
+//! into a single four-byte UTF-8 sequence.
+
+use std::fmt;
+
+/// An error produced while decoding a modified UTF-8 byte sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The byte stream ended in the middle of a multi-byte sequence.
+    UnexpectedEnd,
+    /// A byte did not match any valid MUTF-8 lead or continuation pattern.
+    InvalidByte(u8),
+    /// The decoded UTF-16 code units did not form valid UTF-16 (e.g. an unpaired surrogate).
+    InvalidUtf16,
+    /// The requested string ID has no entry in the string pool.
+    UnknownStringId(u32),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => write!(f, "unexpected end of modified UTF-8 byte stream"),
+            Self::InvalidByte(byte) => write!(f, "invalid modified UTF-8 byte: {:#04x}", byte),
+            Self::InvalidUtf16 => write!(f, "modified UTF-8 bytes decoded to invalid UTF-16"),
+            Self::UnknownStringId(index) => write!(f, "unknown string ID: {}", index),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Decodes a modified UTF-8 byte slice into a `String`.
+///
+/// Returns [`DecodeError`] if `bytes` contains a malformed sequence or decodes to invalid
+/// UTF-16 (such as an unpaired surrogate).
+pub fn decode(bytes: &[u8]) -> Result<String, DecodeError> {
+    let units = decode_to_utf16(bytes)?;
+    String::from_utf16(&units).map_err(|_| DecodeError::InvalidUtf16)
+}
+
+/// Decodes a modified UTF-8 byte slice into a `String`, replacing malformed sequences and
+/// unpaired surrogates with the Unicode replacement character.
+pub fn decode_lossy(bytes: &[u8]) -> String {
+    let units = decode_to_utf16_lossy(bytes);
+    String::from_utf16_lossy(&units)
+}
+
+fn decode_to_utf16(bytes: &[u8]) -> Result<Vec<u16>, DecodeError> {
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut rest = bytes;
+    while let Some(&lead) = rest.first() {
+        let (unit, consumed) = decode_one(rest)?;
+        units.push(unit);
+        rest = &rest[consumed..];
+        let _ = lead;
+    }
+    Ok(units)
+}
+
+fn decode_to_utf16_lossy(bytes: &[u8]) -> Vec<u16> {
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        match decode_one(rest) {
+            Ok((unit, consumed)) => {
+                units.push(unit);
+                rest = &rest[consumed..];
+            }
+            Err(_) => {
+                units.push(0xFFFD);
+                rest = &rest[1..];
+            }
+        }
+    }
+    units
+}
+
+/// Decodes a single MUTF-8 code unit, returning it along with the number of bytes consumed.
+fn decode_one(bytes: &[u8]) -> Result<(u16, usize), DecodeError> {
+    let lead = bytes[0];
+    match lead {
+        0x00..=0x7F => Ok((u16::from(lead), 1)),
+        0xC0..=0xDF => {
+            let b1 = *bytes.get(1).ok_or(DecodeError::UnexpectedEnd)?;
+            if b1 & 0xC0 != 0x80 {
+                return Err(DecodeError::InvalidByte(b1));
+            }
+            let unit = (u16::from(lead & 0x1F) << 6) | u16::from(b1 & 0x3F);
+            Ok((unit, 2))
+        }
+        0xE0..=0xEF => {
+            let b1 = *bytes.get(1).ok_or(DecodeError::UnexpectedEnd)?;
+            let b2 = *bytes.get(2).ok_or(DecodeError::UnexpectedEnd)?;
+            if b1 & 0xC0 != 0x80 {
+                return Err(DecodeError::InvalidByte(b1));
+            }
+            if b2 & 0xC0 != 0x80 {
+                return Err(DecodeError::InvalidByte(b2));
+            }
+            let unit = (u16::from(lead & 0x0F) << 12)
+                | (u16::from(b1 & 0x3F) << 6)
+                | u16::from(b2 & 0x3F);
+            Ok((unit, 3))
+        }
+        _ => Err(DecodeError::InvalidByte(lead)),
+    }
+}
+
+/// Reads an unsigned LEB128 value, returning it along with the number of bytes consumed.
+fn read_uleb128(bytes: &[u8]) -> Result<(u32, usize), DecodeError> {
+    let mut result: u32 = 0;
+    for (i, &byte) in bytes.iter().enumerate().take(5) {
+        result |= u32::from(byte & 0x7F) << (i * 7);
+        if byte & 0x80 == 0 {
+            return Ok((result, i + 1));
+        }
+    }
+    Err(DecodeError::UnexpectedEnd)
+}
+
+/// A resolver for DEX `string_data_item`s, mapping string IDs to their decoded contents.
+///
+/// `offsets` is the `string_ids` section of a parsed DEX file: for each string ID, the byte
+/// offset of its `string_data_item` within `string_data`. Each `string_data_item` is a
+/// `uleb128` UTF-16 size followed by MUTF-8 bytes terminated by a single `0x00` byte.
+#[derive(Debug, Clone, Copy)]
+pub struct StringPool<'a> {
+    string_data: &'a [u8],
+    offsets: &'a [u32],
+}
+
+impl<'a> StringPool<'a> {
+    /// Creates a new string pool over `string_data`, indexed by `offsets`.
+    pub fn new(string_data: &'a [u8], offsets: &'a [u32]) -> Self {
+        Self {
+            string_data,
+            offsets,
+        }
+    }
+
+    /// Resolves the string at `index`, decoding its modified UTF-8 bytes.
+    pub fn get(&self, index: u32) -> Result<String, DecodeError> {
+        decode(self.raw(index)?)
+    }
+
+    /// Resolves the string at `index`, replacing malformed sequences with the replacement
+    /// character. Returns `None` if `index` has no entry in the pool, or if the entry's
+    /// offset or `uleb128` size header lies past the end of the string data.
+    pub fn get_lossy(&self, index: u32) -> Option<String> {
+        self.raw(index).ok().map(decode_lossy)
+    }
+
+    fn raw(&self, index: u32) -> Result<&'a [u8], DecodeError> {
+        let offset = *self
+            .offsets
+            .get(index as usize)
+            .ok_or(DecodeError::UnknownStringId(index))?;
+        let data = self
+            .string_data
+            .get(offset as usize..)
+            .ok_or(DecodeError::UnexpectedEnd)?;
+        let (_utf16_size, header_len) = read_uleb128(data)?;
+        let data = &data[header_len..];
+        let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+        Ok(&data[..end])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, decode_lossy, StringPool};
+
+    #[test]
+    fn it_decodes_plain_ascii() {
+        assert_eq!("hello", decode(b"hello").unwrap());
+    }
+
+    #[test]
+    fn it_decodes_the_two_byte_nul_encoding() {
+        assert_eq!("a\0b", decode(b"a\xC0\x80b").unwrap());
+    }
+
+    #[test]
+    fn it_decodes_a_surrogate_pair_of_three_byte_sequences() {
+        // U+1F600 GRINNING FACE, encoded as the surrogate pair D83D DE00, each written as
+        // its own three-byte sequence rather than one four-byte UTF-8 sequence.
+        let bytes = [0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80];
+        assert_eq!("\u{1F600}", decode(&bytes).unwrap());
+    }
+
+    #[test]
+    fn it_falls_back_to_the_replacement_character_for_malformed_input() {
+        assert_eq!("a\u{FFFD}", decode_lossy(b"a\xFF"));
+    }
+
+    #[test]
+    fn it_resolves_strings_through_the_pool() {
+        // Two string_data_items: utf16_size=5 "hello\0", utf16_size=3 "bye\0".
+        let data = b"\x05hello\0\x03bye\0";
+        let offsets = [0, 7];
+        let pool = StringPool::new(data, &offsets);
+
+        assert_eq!("hello", pool.get(0).unwrap());
+        assert_eq!("bye", pool.get(1).unwrap());
+        assert!(pool.get(2).is_err());
+    }
+}
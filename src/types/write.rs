@@ -0,0 +1,273 @@
+//! Serializing decoded value trees to text.
+//!
+//! Mirrors the reader/writer split used by crates like `preserves`: the [`Writer`] trait
+//! describes how to emit each kind of [`Value`], and [`TextWriter`] is the built-in
+//! implementation that renders a readable, smali-like syntax.
+
+use super::mutf8::StringPool;
+use super::{Annotation, Array, EncodedAnnotation, Value};
+use std::fmt::{self, Write as _};
+
+/// Writes a decoded value tree out to some sink.
+///
+/// Implementors define how each kind of [`Value`] is rendered. [`TextWriter`] is generic over
+/// any `fmt::Write` sink, such as a `String` or a `fmt::Formatter`; an `io::Write` sink such as
+/// a file is not accepted directly.
+pub trait Writer {
+    /// The error type produced when writing fails.
+    type Error;
+
+    /// Writes a boolean literal.
+    fn write_bool(&mut self, value: bool) -> Result<(), Self::Error>;
+    /// Writes a byte literal.
+    fn write_byte(&mut self, value: i8) -> Result<(), Self::Error>;
+    /// Writes a short literal.
+    fn write_short(&mut self, value: i16) -> Result<(), Self::Error>;
+    /// Writes a char literal.
+    fn write_char(&mut self, value: u16) -> Result<(), Self::Error>;
+    /// Writes an int literal.
+    fn write_int(&mut self, value: i32) -> Result<(), Self::Error>;
+    /// Writes a long literal.
+    fn write_long(&mut self, value: i64) -> Result<(), Self::Error>;
+    /// Writes a float literal.
+    fn write_float(&mut self, value: f32) -> Result<(), Self::Error>;
+    /// Writes a double literal.
+    fn write_double(&mut self, value: f64) -> Result<(), Self::Error>;
+    /// Writes the null literal.
+    fn write_null(&mut self) -> Result<(), Self::Error>;
+    /// Writes a string, given its index into the string IDs list.
+    fn write_string(&mut self, index: u32) -> Result<(), Self::Error>;
+    /// Writes a type, given its index into the type IDs list.
+    fn write_type(&mut self, index: u32) -> Result<(), Self::Error>;
+    /// Writes a field, given its index into the field IDs list.
+    fn write_field(&mut self, index: u32) -> Result<(), Self::Error>;
+    /// Writes a method, given its index into the method IDs list.
+    fn write_method(&mut self, index: u32) -> Result<(), Self::Error>;
+    /// Writes an enum value, given its index into the field IDs list.
+    fn write_enum(&mut self, index: u32) -> Result<(), Self::Error>;
+    /// Writes an array of values.
+    fn write_array(&mut self, array: &Array) -> Result<(), Self::Error>;
+    /// Writes an annotation.
+    fn write_annotation(&mut self, annotation: &EncodedAnnotation) -> Result<(), Self::Error>;
+
+    /// Writes any [`Value`], dispatching to the method matching its kind.
+    fn write_value(&mut self, value: &Value) -> Result<(), Self::Error> {
+        match value {
+            Value::Boolean(value) => self.write_bool(*value),
+            Value::Byte(value) => self.write_byte(*value),
+            Value::Short(value) => self.write_short(*value),
+            Value::Char(value) => self.write_char(*value),
+            Value::Int(value) => self.write_int(*value),
+            Value::Long(value) => self.write_long(*value),
+            Value::Float(value) => self.write_float(*value),
+            Value::Double(value) => self.write_double(*value),
+            Value::Null => self.write_null(),
+            Value::String(index) => self.write_string(*index),
+            Value::Type(index) => self.write_type(*index),
+            Value::Field(index) => self.write_field(*index),
+            Value::Method(index) => self.write_method(*index),
+            Value::Enum(index) => self.write_enum(*index),
+            Value::Array(array) => self.write_array(array),
+            Value::Annotation(annotation) => self.write_annotation(annotation),
+        }
+    }
+}
+
+/// Renders a value tree as readable, smali-like text (e.g. `@7(@9 = true)`).
+///
+/// Strings are rendered via their resolved contents when a [`StringPool`] is supplied,
+/// falling back to `@index` otherwise. Types, fields, methods and enums are always rendered
+/// as `@index`, since resolving them to a name requires the type/field/method ID tables,
+/// which are outside the string pool this writer resolves against.
+pub struct TextWriter<'a, W> {
+    sink: W,
+    pool: Option<&'a StringPool<'a>>,
+}
+
+impl<'a, W: fmt::Write> TextWriter<'a, W> {
+    /// Creates a writer with no string pool; indexed values render as `@index`.
+    pub fn new(sink: W) -> Self {
+        Self { sink, pool: None }
+    }
+
+    /// Creates a writer that resolves strings and annotation element names to their
+    /// contents through `pool`.
+    pub fn with_pool(sink: W, pool: &'a StringPool<'a>) -> Self {
+        Self {
+            sink,
+            pool: Some(pool),
+        }
+    }
+
+    /// Consumes the writer, returning the underlying sink.
+    pub fn into_inner(self) -> W {
+        self.sink
+    }
+
+    /// Writes a name resolved through the string pool, given its index into the string IDs
+    /// list, falling back to `@index` if it can't be resolved.
+    fn write_string_index(&mut self, index: u32) -> fmt::Result {
+        match self.pool.and_then(|pool| pool.get(index).ok()) {
+            Some(name) => write!(self.sink, "{}", name),
+            None => write!(self.sink, "@{}", index),
+        }
+    }
+}
+
+impl<'a, W: fmt::Write> Writer for TextWriter<'a, W> {
+    type Error = fmt::Error;
+
+    fn write_bool(&mut self, value: bool) -> fmt::Result {
+        write!(self.sink, "{}", value)
+    }
+
+    fn write_byte(&mut self, value: i8) -> fmt::Result {
+        write!(self.sink, "{}", value)
+    }
+
+    fn write_short(&mut self, value: i16) -> fmt::Result {
+        write!(self.sink, "{}", value)
+    }
+
+    fn write_char(&mut self, value: u16) -> fmt::Result {
+        write!(self.sink, "{}", value)
+    }
+
+    fn write_int(&mut self, value: i32) -> fmt::Result {
+        write!(self.sink, "{}", value)
+    }
+
+    fn write_long(&mut self, value: i64) -> fmt::Result {
+        write!(self.sink, "{}", value)
+    }
+
+    fn write_float(&mut self, value: f32) -> fmt::Result {
+        write!(self.sink, "{}", value)
+    }
+
+    fn write_double(&mut self, value: f64) -> fmt::Result {
+        write!(self.sink, "{}", value)
+    }
+
+    fn write_null(&mut self) -> fmt::Result {
+        write!(self.sink, "null")
+    }
+
+    fn write_string(&mut self, index: u32) -> fmt::Result {
+        match self.pool.and_then(|pool| pool.get(index).ok()) {
+            Some(value) => write!(self.sink, "{:?}", value),
+            None => write!(self.sink, "@{}", index),
+        }
+    }
+
+    fn write_type(&mut self, index: u32) -> fmt::Result {
+        // `index` is into the type IDs list, a different ID space from the string pool this
+        // writer resolves against, so it can only be rendered as a raw reference.
+        write!(self.sink, "@{}", index)
+    }
+
+    fn write_field(&mut self, index: u32) -> fmt::Result {
+        // `index` is into the field IDs list; see `write_type`.
+        write!(self.sink, "@{}", index)
+    }
+
+    fn write_method(&mut self, index: u32) -> fmt::Result {
+        // `index` is into the method IDs list; see `write_type`.
+        write!(self.sink, "@{}", index)
+    }
+
+    fn write_enum(&mut self, index: u32) -> fmt::Result {
+        // `index` is into the field IDs list; see `write_type`.
+        write!(self.sink, "@{}", index)
+    }
+
+    fn write_array(&mut self, array: &Array) -> fmt::Result {
+        write!(self.sink, "{{ ")?;
+        for (i, value) in array.values().iter().enumerate() {
+            if i > 0 {
+                write!(self.sink, ", ")?;
+            }
+            self.write_value(value)?;
+        }
+        write!(self.sink, " }}")
+    }
+
+    fn write_annotation(&mut self, annotation: &EncodedAnnotation) -> fmt::Result {
+        self.write_type(annotation.type_index())?;
+        write!(self.sink, "(")?;
+        for (i, element) in annotation.elements().iter().enumerate() {
+            if i > 0 {
+                write!(self.sink, ", ")?;
+            }
+            self.write_string_index(element.name_index())?;
+            write!(self.sink, " = ")?;
+            self.write_value(element)?;
+        }
+        write!(self.sink, ")")
+    }
+}
+
+impl EncodedAnnotation {
+    /// Serializes this annotation through `writer`.
+    pub fn write<W: Writer>(&self, writer: &mut W) -> Result<(), W::Error> {
+        writer.write_annotation(self)
+    }
+}
+
+impl Annotation {
+    /// Serializes this annotation through `writer`.
+    pub fn write<W: Writer>(&self, writer: &mut W) -> Result<(), W::Error> {
+        writer.write_annotation(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TextWriter, Writer};
+    use crate::types::{AnnotationElement, Array, EncodedAnnotation, Value};
+
+    #[test]
+    fn it_writes_scalar_values_as_literals() {
+        let mut out = String::new();
+        let mut writer = TextWriter::new(&mut out);
+
+        writer.write_value(&Value::Int(42)).unwrap();
+
+        assert_eq!("42", out);
+    }
+
+    #[test]
+    fn it_writes_indexed_values_as_index_references_without_a_pool() {
+        let mut out = String::new();
+        let mut writer = TextWriter::new(&mut out);
+
+        writer.write_value(&Value::Type(3)).unwrap();
+
+        assert_eq!("@3", out);
+    }
+
+    #[test]
+    fn it_writes_an_array_as_braces() {
+        let mut out = String::new();
+        let mut writer = TextWriter::new(&mut out);
+        let array = Array::new(vec![Value::Int(1), Value::Int(2)]);
+
+        writer.write_value(&Value::Array(array)).unwrap();
+
+        assert_eq!("{ 1, 2 }", out);
+    }
+
+    #[test]
+    fn it_writes_an_annotation_in_smali_like_syntax() {
+        let mut out = String::new();
+        let mut writer = TextWriter::new(&mut out);
+        let annotation = EncodedAnnotation::new(
+            7,
+            vec![AnnotationElement::new(9, Value::Boolean(true))].into_boxed_slice(),
+        );
+
+        annotation.write(&mut writer).unwrap();
+
+        assert_eq!("@7(@9 = true)", out);
+    }
+}